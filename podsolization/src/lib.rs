@@ -1,11 +1,66 @@
-use std::{error::Error, future::Future, marker::PhantomData, sync::Arc};
+use std::{collections::{HashMap, HashSet}, error::Error, fmt, future::Future, marker::PhantomData, pin::Pin, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
 // Analagous to a configured terraform provider.
 // Any common configuration for managing resources associated with this provider.
 // It is recommended to use the `secrecy` library or some alternative to protect user secrets.
-pub trait Provider {}
+pub trait Provider {
+    // The optional features this provider supports, as configured (e.g. a newer API version).
+    // Defaults to none.
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::new()
+    }
+}
+
+// A named provider feature that a resource type may depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capability(pub &'static str);
+
+// How an error returned by a provider should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // A temporary failure. The operation may succeed if retried.
+    Transient,
+    // The provider rejected our credentials or permissions.
+    Auth,
+    // The resource doesn't exist.
+    NotFound,
+    // The provider is throttling us. Retry after the given delay, if it told us one.
+    RateLimited { retry_after: Option<Duration> },
+    // The operation will not succeed no matter how many times it is retried.
+    Permanent,
+}
+
+// An error returned by a resource operation.
+// Classifying errors here means retry, rollback, and not-found handling can be decided in
+// one place rather than separately for each resource type. Defaults to `Permanent`, so
+// nothing is retried unless the error type says it can be.
+pub trait ProviderError: Error {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Permanent
+    }
+}
+
+// Why a user-supplied string couldn't be turned into a resource identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportParseError {
+    // The resource type doesn't support importing from a string id.
+    Unsupported,
+    // The string isn't a valid identifier for the resource type. Holds an explanation.
+    Invalid(String),
+}
+
+impl fmt::Display for ImportParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportParseError::Unsupported => write!(f, "resource type does not support importing by id"),
+            ImportParseError::Invalid(reason) => write!(f, "invalid resource id: {reason}"),
+        }
+    }
+}
+
+impl Error for ImportParseError {}
 
 // A specific type of provider resource. For example, a VPC.
 #[trait_variant::make(ResourceType: Send + Sync)]
@@ -18,13 +73,15 @@ pub trait LocalResourceType<'a> {
     // Information about this resource's state obtained from the provider
     type ResourceState: Serialize + Deserialize<'a>;
 
-    // Enough info to locate the resource so it can be read, updated, or deleted
+    // Enough info to locate the resource so it can be read, updated, or deleted.
+    // This doesn't have to be a single id. For list-style resources it can be a structured
+    // query (filters, an optional pagination cursor) that `get` uses to find its contents.
     type ResourceIdentifier: Serialize + Deserialize<'a>;
 
-    type CreateError: Error;
-    type GetError: Error;
-    type UpdateError: Error;
-    type DeleteError: Error;
+    type CreateError: ProviderError;
+    type GetError: ProviderError;
+    type UpdateError: ProviderError;
+    type DeleteError: ProviderError;
 
     // Create a new 
     // Not all providers will need a reqwest client, but it's common enough that
@@ -65,15 +122,214 @@ pub trait LocalResourceType<'a> {
         client: reqwest::Client,
         provider: Self::ResourceProvider
     ) -> Result<(), Self::DeleteError>;
+
+    // The tags currently applied to the resource, as reported in its state.
+    // Tags are often managed through a separate API from the rest of the spec, so this lets
+    // tag drift be detected and reconciled on its own. Defaults to no tags.
+    fn tags_from_state(state: &Self::ResourceState) -> HashMap<String, String> {
+        let _ = state;
+        HashMap::new()
+    }
+
+    // Check that a freshly created resource is actually usable. Some providers report success
+    // from `create` before the resource is consistent, or after a silent partial failure.
+    // An error here means the creation should be treated as failed. Defaults to no checks.
+    fn post_create_verify(
+        id: &Self::ResourceIdentifier,
+        state: &Self::ResourceState,
+        client: reqwest::Client,
+        provider: Self::ResourceProvider
+    ) -> impl Future<Output = Result<(), Self::GetError>> {
+        let _ = (id, state, client, provider);
+        async { Ok(()) }
+    }
+
+    // Rewrite a state returned by the provider into a canonical form.
+    // Providers are often inconsistent (null vs. missing fields, differently-cased enums),
+    // which would otherwise show up as spurious differences. This should be applied to every
+    // state returned by `create`, `get`, or `update` before it is compared with anything.
+    // Defaults to leaving the state as-is.
+    fn normalize_state(state: &mut Self::ResourceState) {
+        let _ = state;
+    }
+
+    // Wait until a deleted resource is actually gone.
+    // Many providers accept a delete and then leave the resource in a "deleting" state for a
+    // while, and its dependencies can't be removed until it's finished. Defaults to assuming
+    // a successful `delete` takes effect immediately.
+    fn wait_until_deleted(
+        id: &Self::ResourceIdentifier,
+        client: reqwest::Client,
+        provider: Self::ResourceProvider
+    ) -> impl Future<Output = Result<(), Self::DeleteError>> {
+        let _ = (id, client, provider);
+        async { Ok(()) }
+    }
+
+    // Check that the identified resource could be deleted (permissions, locks held by other
+    // resources, etc.) without actually deleting it, so a destroy can be validated before
+    // anything is removed. Defaults to assuming the delete would succeed.
+    fn can_delete(
+        id: &Self::ResourceIdentifier,
+        client: reqwest::Client,
+        provider: Self::ResourceProvider
+    ) -> impl Future<Output = Result<(), Self::DeleteError>> {
+        let _ = (id, client, provider);
+        async { Ok(()) }
+    }
+
+    // The identifier a resource will have, if it can be known before it is created.
+    // Some providers let the client choose the id (e.g. a PUT to a known path), in which case
+    // `create` can be made idempotent and dependents can refer to the resource before `create`
+    // returns. Defaults to `None`, meaning the provider assigns the id.
+    fn compute_identifier(spec: &Self::ResourceSpec) -> Option<Self::ResourceIdentifier> {
+        let _ = spec;
+        None
+    }
+
+    // Whether at most one resource of this type may exist per account/region, such as a
+    // default VPC or an account-level setting. A graph declaring more than one is invalid.
+    fn is_singleton() -> bool {
+        false
+    }
+
+    // Parse an identifier typed by a user, e.g. as a CLI argument to an import command.
+    // Defaults to `ImportParseError::Unsupported`.
+    fn import_id_from_string(s: &str) -> Result<Self::ResourceIdentifier, ImportParseError> {
+        let _ = s;
+        Err(ImportParseError::Unsupported)
+    }
+
+    // The provider's canonical name for the resource (e.g. an ARN or resource URI), for
+    // interop with other tools. This is separate from the opaque `ResourceIdentifier`.
+    // Defaults to `None`.
+    fn native_urn(state: &Self::ResourceState) -> Option<String> {
+        let _ = state;
+        None
+    }
+
+    // The maximum number of operations on resources of this type that may run at once.
+    // Some provider APIs rate-limit per resource type rather than globally. This applies in
+    // addition to any overall concurrency limit. Defaults to `None` (no per-type limit).
+    fn max_concurrent() -> Option<usize> {
+        None
+    }
+
+    // The spec fields that form the resource's identity (e.g. a name that forms its key).
+    // These can't be changed in place, so a change to any of them forces the resource to be
+    // replaced. Defaults to none.
+    fn identity_fields() -> &'static [&'static str] {
+        &[]
+    }
+
+    // Build the spec to send in an update from the desired spec and the current state.
+    // Providers sometimes manage fields themselves (e.g. automatically-added default tags), and
+    // a blind update with the desired spec would wipe them out. Defaults to the desired spec.
+    fn merge_state(current: &Self::ResourceState, desired_spec: Self::ResourceSpec) -> Self::ResourceSpec {
+        let _ = current;
+        desired_spec
+    }
+
+    // The provider capabilities this resource type needs. A resource whose provider lacks any
+    // of these can't be managed, which is better caught before making any calls than deep
+    // inside `create`. Defaults to none.
+    fn required_capabilities() -> &'static [Capability] {
+        &[]
+    }
+}
+
+// The capabilities a resource type requires that its provider doesn't support, if any.
+// A resource with missing capabilities can't be managed, so this should be checked before any
+// calls are made to the provider.
+pub fn missing_capabilities<'a, R: ResourceType<'a>>(provider: &R::ResourceProvider) -> Vec<Capability> {
+    let supported = provider.capabilities();
+    R::required_capabilities()
+        .iter()
+        .filter(|capability| !supported.contains(capability))
+        .copied()
+        .collect()
+}
+
+// For resource types whose provider can disable a resource rather than destroying it.
+// Resource types that don't implement this are skipped (with a warning) when disabling.
+pub trait Disable<'a>: ResourceType<'a> {
+    // Disable the identified resource instead of destroying it, then return the resulting
+    // state if successful.
+    fn disable(
+        id: Self::ResourceIdentifier,
+        client: reqwest::Client,
+        provider: Self::ResourceProvider
+    ) -> impl Future<Output = Result<Self::ResourceState, Self::UpdateError>> + Send;
 }
 
+// For resource types whose spec is a subset of their state.
+// Implementing this saves copying fields by hand when checking whether an existing resource
+// already matches its spec.
+pub trait SpecFromState<'a>: ResourceType<'a> {
+    // Project a state down to the spec that would produce it.
+    fn spec_from_state(state: &Self::ResourceState) -> Self::ResourceSpec;
+
+    // Whether the resource described by `state` already matches `spec`.
+    fn spec_matches_state(spec: &Self::ResourceSpec, state: &Self::ResourceState) -> bool
+        where Self::ResourceSpec: PartialEq
+    {
+        Self::spec_from_state(state) == *spec
+    }
+}
+
+// Analagous to a terraform data source.
+// A read-only lookup of something this crate doesn't manage, such as an existing machine image.
+// Its output can be depended on like a resource's, but it is never created, updated, or deleted.
+#[trait_variant::make(DataSource: Send + Sync)]
+pub trait LocalDataSource<'a> {
+    type DataProvider: Provider;
+
+    // The information read from the provider
+    type Data: Serialize + Deserialize<'a>;
+
+    type ReadError: ProviderError;
+
+    // Read the data from the provider.
+    // Not all providers will need a reqwest client, but it's common enough that
+    // it's included here. Might result in some extra boilerplate for uncommon use cases.
+    // If you need something else, consider putting it in the DataProvider
+    async fn read(
+        client: reqwest::Client,
+        provider: Self::DataProvider
+    ) -> Result<Self::Data, Self::ReadError>;
+}
+
+// The in-progress creation of a resource, resolving to its state once the provider is done.
+pub type BuildFuture<'a, R> = Pin<Box<
+    dyn Future<Output = Result<<R as ResourceType<'a>>::ResourceState, <R as ResourceType<'a>>::CreateError>>
+        + Send + 'a
+>>;
+
 pub enum Resource<'a, R: ResourceType<'a>, DL: DependencyList<'a>, RB: ResourceBuilder<'a, R, DL>> {
     AwaitingDeps(DL),
-    Building(R::ResourceSpec, Box<dyn Future<Output = R::ResourceState>>),
+    Building(R::ResourceSpec, BuildFuture<'a, R>),
     Done(R::ResourceSpec, R::ResourceState),
+    // Building the resource failed. The spec is kept so the build can be retried.
+    Failed(R::ResourceSpec),
     _NotUsed(PhantomData<RB>),
 }
 
+impl<'a, R: ResourceType<'a>, DL: DependencyList<'a>, RB: ResourceBuilder<'a, R, DL>> Resource<'a, R, DL, RB> {
+    // Wait for a resource that is being built to finish, moving it to `Done`.
+    // This takes the contents of a `Building` resource, so success always means `Done`.
+    // If the build fails, the resource is moved to `Failed` and returned along with the error,
+    // so the caller can decide whether to retry or roll back without losing track of it.
+    pub async fn poll_to_done(
+        spec: R::ResourceSpec,
+        building: BuildFuture<'a, R>
+    ) -> Result<Self, (Self, R::CreateError)> {
+        match building.await {
+            Ok(state) => Ok(Resource::Done(spec, state)),
+            Err(e) => Err((Resource::Failed(spec), e)),
+        }
+    }
+}
+
 pub struct Dependency<'a, R: ResourceType<'a>> {
     state: Arc<R::ResourceSpec>
 }
@@ -86,12 +342,26 @@ impl<'a, R: ResourceType<'a>> Serialize for Dependency<'a, R> {
     }
 }
 
+pub struct DataDependency<'a, D: DataSource<'a>> {
+    data: Arc<D::Data>
+}
+
+impl<'a, D: DataSource<'a>> Serialize for DataDependency<'a, D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        self.data.serialize(serializer)
+    }
+}
+
 trait DependencyTupleTrait<'a> {}
 
 impl<'a> DependencyTupleTrait<'a> for () {}
 
 impl<'a, Tail: DependencyTupleTrait<'a>, R: ResourceType<'a> > DependencyTupleTrait<'a> for (Dependency<'a, R>, Tail) {}
 
+impl<'a, Tail: DependencyTupleTrait<'a>, D: DataSource<'a> > DependencyTupleTrait<'a> for (DataDependency<'a, D>, Tail) {}
+
 // A collection of dependencies. Under the hood, we use tuple structs for type checking and
 // iteration, but we don't want users to have to work with tuple structs when building resources.
 // TODO: write a derive macro for this.
@@ -115,9 +385,355 @@ pub trait LocalResourceBuilder<'a, R: ResourceType<'a>, DL: DependencyList<'a>>
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TestSpec {
+        name: String,
+        tags: HashMap<String, String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TestState {
+        name: String,
+        status: String,
+        tags: HashMap<String, String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestId(String);
+
+    #[derive(Debug, PartialEq)]
+    struct TestError;
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "test error")
+        }
+    }
+
+    impl Error for TestError {}
+
+    impl ProviderError for TestError {}
 
-    // #[test]
-    // fn it_works() {
-    // }
+    #[derive(Debug)]
+    struct RateLimitedError(Duration);
+
+    impl fmt::Display for RateLimitedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "rate limited, retry after {:?}", self.0)
+        }
+    }
+
+    impl Error for RateLimitedError {}
+
+    impl ProviderError for RateLimitedError {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::RateLimited { retry_after: Some(self.0) }
+        }
+    }
+
+    struct TestProvider;
+
+    impl Provider for TestProvider {}
+
+    struct TestType;
+
+    impl<'a> ResourceType<'a> for TestType {
+        type ResourceProvider = TestProvider;
+        type ResourceSpec = TestSpec;
+        type ResourceState = TestState;
+        type ResourceIdentifier = TestId;
+        type CreateError = TestError;
+        type GetError = TestError;
+        type UpdateError = TestError;
+        type DeleteError = TestError;
+
+        async fn create(_: reqwest::Client, _: TestProvider) -> Result<(TestId, TestState), TestError> {
+            Err(TestError)
+        }
+
+        async fn get(_: TestId, _: reqwest::Client, _: TestProvider) -> Result<TestState, TestError> {
+            Err(TestError)
+        }
+
+        async fn update(_: TestId, _: TestSpec, _: reqwest::Client, _: TestProvider) -> Result<TestState, TestError> {
+            Err(TestError)
+        }
+
+        async fn delete(_: TestId, _: reqwest::Client, _: TestProvider) -> Result<(), TestError> {
+            Err(TestError)
+        }
+
+        fn import_id_from_string(s: &str) -> Result<TestId, ImportParseError> {
+            match s.strip_prefix("test-") {
+                Some(rest) if !rest.is_empty() => Ok(TestId(s.to_string())),
+                _ => Err(ImportParseError::Invalid(format!("{s:?} does not start with \"test-\""))),
+            }
+        }
+
+        fn required_capabilities() -> &'static [Capability] {
+            &[Capability("v2-api")]
+        }
+
+        // The provider reports statuses in whatever case it likes.
+        fn normalize_state(state: &mut TestState) {
+            state.status = state.status.to_lowercase();
+        }
+
+        // The provider adds a `managed-by` tag to everything it creates. Keep it on updates.
+        fn merge_state(current: &TestState, mut desired_spec: TestSpec) -> TestSpec {
+            if let Some(managed_by) = current.tags.get("managed-by") {
+                desired_spec.tags.entry("managed-by".to_string()).or_insert_with(|| managed_by.clone());
+            }
+            desired_spec
+        }
+    }
+
+    // A resource type that relies on every default.
+    struct DefaultType;
+
+    impl<'a> ResourceType<'a> for DefaultType {
+        type ResourceProvider = TestProvider;
+        type ResourceSpec = TestSpec;
+        type ResourceState = TestState;
+        type ResourceIdentifier = TestId;
+        type CreateError = TestError;
+        type GetError = TestError;
+        type UpdateError = TestError;
+        type DeleteError = TestError;
+
+        async fn create(_: reqwest::Client, _: TestProvider) -> Result<(TestId, TestState), TestError> {
+            Err(TestError)
+        }
+
+        async fn get(_: TestId, _: reqwest::Client, _: TestProvider) -> Result<TestState, TestError> {
+            Err(TestError)
+        }
+
+        async fn update(_: TestId, _: TestSpec, _: reqwest::Client, _: TestProvider) -> Result<TestState, TestError> {
+            Err(TestError)
+        }
+
+        async fn delete(_: TestId, _: reqwest::Client, _: TestProvider) -> Result<(), TestError> {
+            Err(TestError)
+        }
+    }
+
+    impl<'a> Disable<'a> for TestType {
+        async fn disable(id: TestId, _: reqwest::Client, _: TestProvider) -> Result<TestState, TestError> {
+            Ok(state(&id.0, "disabled"))
+        }
+    }
+
+    impl<'a> SpecFromState<'a> for TestType {
+        fn spec_from_state(state: &TestState) -> TestSpec {
+            TestSpec { name: state.name.clone(), tags: state.tags.clone() }
+        }
+    }
+
+    struct NoDeps;
+
+    impl<'a> DependencyList<'a> for NoDeps {
+        type DependencyTuple = ();
+
+        fn tupleify(self) -> Self::DependencyTuple {}
+
+        fn detupleify(_: Self::DependencyTuple) -> Self {
+            NoDeps
+        }
+    }
+
+    struct TestBuilder;
+
+    impl<'a> ResourceBuilder<'a, TestType, NoDeps> for TestBuilder {
+        async fn build_spec(_: NoDeps) -> TestSpec {
+            spec("web")
+        }
+    }
+
+    type TestResource = Resource<'static, TestType, NoDeps, TestBuilder>;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TestImage {
+        id: String,
+    }
+
+    struct TestImageSource;
+
+    impl<'a> DataSource<'a> for TestImageSource {
+        type DataProvider = TestProvider;
+        type Data = TestImage;
+        type ReadError = TestError;
+
+        async fn read(_: reqwest::Client, _: TestProvider) -> Result<TestImage, TestError> {
+            Ok(TestImage { id: "image-123".to_string() })
+        }
+    }
+
+    // A resource that depends on both a data source and another resource.
+    struct ImageAndNetwork<'a> {
+        image: DataDependency<'a, TestImageSource>,
+        network: Dependency<'a, TestType>,
+    }
+
+    impl<'a> DependencyList<'a> for ImageAndNetwork<'a> {
+        type DependencyTuple = (DataDependency<'a, TestImageSource>, (Dependency<'a, TestType>, ()));
+
+        fn tupleify(self) -> Self::DependencyTuple {
+            (self.image, (self.network, ()))
+        }
+
+        fn detupleify((image, (network, ())): Self::DependencyTuple) -> Self {
+            ImageAndNetwork { image, network }
+        }
+    }
+
+    struct InstanceBuilder;
+
+    impl<'a> ResourceBuilder<'a, TestType, ImageAndNetwork<'a>> for InstanceBuilder {
+        async fn build_spec(dependencies: ImageAndNetwork<'a>) -> TestSpec {
+            let mut instance = spec(&format!("{}-instance", dependencies.network.state.name));
+            instance.tags.insert("image".to_string(), dependencies.image.data.id.clone());
+            instance
+        }
+    }
+
+    fn spec(name: &str) -> TestSpec {
+        TestSpec { name: name.to_string(), tags: HashMap::new() }
+    }
+
+    fn state(name: &str, status: &str) -> TestState {
+        TestState { name: name.to_string(), status: status.to_string(), tags: HashMap::new() }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    // Drive a future that never actually waits to completion.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn poll_to_done_moves_successful_build_to_done() {
+        let building: BuildFuture<'static, TestType> = Box::pin(async { Ok(state("web", "running")) });
+        match block_on(TestResource::poll_to_done(spec("web"), building)) {
+            Ok(Resource::Done(done_spec, done_state)) => {
+                assert_eq!(done_spec, spec("web"));
+                assert_eq!(done_state, state("web", "running"));
+            },
+            _ => panic!("expected a done resource"),
+        }
+    }
+
+    #[test]
+    fn poll_to_done_keeps_spec_of_failed_build() {
+        let building: BuildFuture<'static, TestType> = Box::pin(async { Err(TestError) });
+        match block_on(TestResource::poll_to_done(spec("web"), building)) {
+            Err((Resource::Failed(failed_spec), e)) => {
+                assert_eq!(failed_spec, spec("web"));
+                assert_eq!(e, TestError);
+            },
+            _ => panic!("expected a failed resource"),
+        }
+    }
+
+    #[test]
+    fn spec_matches_state_projects_state_to_spec() {
+        assert!(TestType::spec_matches_state(&spec("web"), &state("web", "running")));
+        assert!(!TestType::spec_matches_state(&spec("web"), &state("db", "running")));
+    }
+
+    #[test]
+    fn merge_state_preserves_server_managed_fields() {
+        let mut current = state("web", "running");
+        current.tags.insert("managed-by".to_string(), "provider".to_string());
+        let mut desired = spec("web");
+        desired.tags.insert("env".to_string(), "prod".to_string());
+
+        let merged = <TestType as ResourceType>::merge_state(&current, desired);
+        assert_eq!(merged.tags.get("managed-by").map(String::as_str), Some("provider"));
+        assert_eq!(merged.tags.get("env").map(String::as_str), Some("prod"));
+    }
+
+    #[test]
+    fn normalize_state_makes_equivalent_states_equal() {
+        let mut first = state("web", "RUNNING");
+        let mut second = state("web", "Running");
+        assert_ne!(first, second);
+
+        <TestType as ResourceType>::normalize_state(&mut first);
+        <TestType as ResourceType>::normalize_state(&mut second);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn import_id_from_string_parses_valid_and_rejects_invalid_ids() {
+        assert_eq!(<TestType as ResourceType>::import_id_from_string("test-web"), Ok(TestId("test-web".to_string())));
+        assert_eq!(
+            <TestType as ResourceType>::import_id_from_string("web"),
+            Err(ImportParseError::Invalid("\"web\" does not start with \"test-\"".to_string()))
+        );
+        assert_eq!(<DefaultType as ResourceType>::import_id_from_string("test-web"), Err(ImportParseError::Unsupported));
+    }
+
+    #[test]
+    fn import_parse_error_display() {
+        assert_eq!(ImportParseError::Unsupported.to_string(), "resource type does not support importing by id");
+        assert_eq!(ImportParseError::Invalid("bad".to_string()).to_string(), "invalid resource id: bad");
+    }
+
+    #[test]
+    fn provider_error_kind_defaults_to_permanent() {
+        assert_eq!(TestError.kind(), ErrorKind::Permanent);
+    }
+
+    #[test]
+    fn provider_error_kind_can_be_overridden() {
+        let e = RateLimitedError(Duration::from_secs(30));
+        assert_eq!(e.kind(), ErrorKind::RateLimited { retry_after: Some(Duration::from_secs(30)) });
+    }
+
+    #[test]
+    fn disable_is_opt_in() {
+        // Only types implementing `Disable` can be disabled. `DefaultType` doesn't, so
+        // `DefaultType::disable` wouldn't compile.
+        let disabled = block_on(TestType::disable(TestId("web".to_string()), reqwest::Client::new(), TestProvider));
+        assert_eq!(disabled, Ok(state("web", "disabled")));
+    }
+
+    #[test]
+    fn missing_capabilities_names_unsupported_requirements() {
+        assert_eq!(missing_capabilities::<TestType>(&TestProvider), vec![Capability("v2-api")]);
+        assert_eq!(missing_capabilities::<DefaultType>(&TestProvider), vec![]);
+    }
+
+    #[test]
+    fn data_source_feeds_resource_builder() {
+        let image = block_on(<TestImageSource as DataSource>::read(reqwest::Client::new(), TestProvider)).unwrap();
+        let deps = ImageAndNetwork {
+            image: DataDependency { data: Arc::new(image) },
+            network: Dependency { state: Arc::new(spec("vpc")) },
+        };
+        let deps = ImageAndNetwork::detupleify(deps.tupleify());
+        let resource = Resource::<'static, TestType, ImageAndNetwork<'static>, InstanceBuilder>::AwaitingDeps(deps);
+
+        let Resource::AwaitingDeps(deps) = resource else { panic!("expected a resource awaiting deps") };
+        let built = block_on(<InstanceBuilder as ResourceBuilder<TestType, _>>::build_spec(deps));
+        assert_eq!(built.name, "vpc-instance");
+        assert_eq!(built.tags.get("image").map(String::as_str), Some("image-123"));
+    }
 }